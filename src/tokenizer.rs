@@ -1,85 +1,394 @@
+use std::any::{Any, TypeId};
+use std::borrow::Cow;
 use std::sync::Arc;
 
-use unicode_segmentation::UnicodeSegmentation;
+use unicode_segmentation::{GraphemeIndices, UnicodeSegmentation};
 
-pub enum Tokenizer {
-    /// A slice based `Tokenizer`
+/// Splits a key `String` into a sequence of tokens of type `K`, and joins
+/// such a sequence back into a `String`.
+///
+/// `K` is the type stored at each `Trie` level. `Slice`, `Grapheme` and
+/// `Delimiter` only make sense for `K = String` (they work by carving up a
+/// `String`), so those three variants are `#[non_exhaustive]` and can't be
+/// constructed outside this crate: build them through
+/// `Tokenizer::<String>::slice`/`grapheme`/`delimiter`, which only exist in
+/// `impl Tokenizer<String>` and so simply don't exist to call for any other
+/// `K`. Use `Tokenizer::custom` when your keys are sequences of structured
+/// tokens (enums, interned symbols, integers) rather than characters or
+/// substrings.
+#[non_exhaustive]
+pub enum Tokenizer<K = String> {
+    /// A byte-window based `Tokenizer`
     ///
-    /// Will tokenize `String` and detokenize `Vec<String>` by `usize` length.
+    /// Splits a key into chunks of at most `length` *bytes* each, without
+    /// splitting a Unicode grapheme cluster across a chunk boundary (a
+    /// chunk may therefore end up shorter than `length` bytes if the next
+    /// grapheme wouldn't fit). A single grapheme cluster longer than
+    /// `length` bytes becomes a chunk of its own, longer than `length`.
+    /// `length` is a byte budget, not a glyph count; to split into an exact
+    /// number of grapheme clusters instead, use `Tokenizer::Grapheme`.
+    #[non_exhaustive]
     Slice(usize),
+    /// A grapheme-cluster based `Tokenizer`
+    ///
+    /// Splits a key into chunks of exactly `length` Unicode grapheme
+    /// clusters each (as determined by `unicode_segmentation`), regardless
+    /// of how many bytes those clusters take up. The last chunk holds
+    /// whatever is left and may be shorter; a `length` of 0 is treated as 1.
+    #[non_exhaustive]
+    Grapheme(usize),
     /// A delimiter based `Tokenizer`
     ///
     /// Will tokenize `String` and detokenize `Vec<String>` using a `String` delimiter.
+    #[non_exhaustive]
     Delimiter(String),
     /// A custom user defined `Tokenizer`
     ///
     /// Arguments
     ///
-    /// `Box<dyn Fn(String) -> Vec<String>>` - A function that will be used to `tokenize` a key into tokens.
-    /// `Box<dyn Fn(Vec<String>) -> String` - A function that will be used to `detokenize` a `Vec<String>` of tokens into a `String`.
+    /// `Arc<dyn Fn(String) -> Vec<K>>` - A function that will be used to `tokenize` a key into tokens.
+    /// `Arc<dyn Fn(Vec<K>) -> String>` - A function that will be used to `detokenize` a `Vec<K>` of tokens into a `String`.
     ///
     /// Will tokenize and detokenize in a user defined way.
-    Custom(Arc<dyn Fn(String) -> Vec<String>>, Arc<dyn Fn(Vec<String>) -> String>)
+    Custom(Arc<dyn Fn(String) -> Vec<K>>, Arc<dyn Fn(Vec<K>) -> String>)
+}
+
+/// Manual `Clone` impl so `Tokenizer<K>` clones regardless of whether `K`
+/// implements `Clone`: every variant is either plain data or an `Arc`,
+/// both of which clone without needing `K: Clone`.
+impl<K> Clone for Tokenizer<K> {
+    fn clone(&self) -> Self {
+        match self {
+            Self::Slice(length) => Self::Slice(*length),
+            Self::Grapheme(length) => Self::Grapheme(*length),
+            Self::Delimiter(delimiter) => Self::Delimiter(delimiter.clone()),
+            Self::Custom(tokenize_fn, detokenize_fn) => Self::Custom(
+                Arc::clone(tokenize_fn),
+                Arc::clone(detokenize_fn)
+            ),
+        }
+    }
 }
 
-impl Tokenizer {
+impl Tokenizer<String> {
+    /// Builds a `Tokenizer::Slice`, splitting keys into byte-window chunks
+    /// of at most `length` bytes; see the variant's doc comment for the
+    /// exact chunking rule.
+    ///
+    /// Only available for `K = String`: `Slice` carves up a `String`
+    /// directly, so this is an inherent method on `Tokenizer<String>`
+    /// rather than a variant constructor callers could otherwise reach for
+    /// any `K`.
+    pub fn slice(length: usize) -> Self {
+        Self::Slice(length)
+    }
+
+    /// Builds a `Tokenizer::Grapheme`, splitting keys into chunks of
+    /// exactly `length` Unicode grapheme clusters.
+    ///
+    /// Only available for `K = String`, for the same reason as `slice`.
+    pub fn grapheme(length: usize) -> Self {
+        Self::Grapheme(length)
+    }
+
+    /// Builds a `Tokenizer::Delimiter`, splitting keys on `delimiter`.
+    ///
+    /// Only available for `K = String`, for the same reason as `slice`.
+    pub fn delimiter(delimiter: String) -> Self {
+        Self::Delimiter(delimiter)
+    }
+}
+
+impl<K> Tokenizer<K> {
+    /// Builds a `Tokenizer::Custom` from a pair of user-supplied
+    /// tokenize/detokenize functions.
+    ///
+    /// Arguments
+    ///
+    /// `tokenize_fn` - `Arc<dyn Fn(String) -> Vec<K>>`, run on each key operation to split keys into tokens.
+    /// `detokenize_fn` - `Arc<dyn Fn(Vec<K>) -> String>`, run to reassemble tokens into a key.
+    pub fn custom(
+        tokenize_fn: Arc<dyn Fn(String) -> Vec<K>>,
+        detokenize_fn: Arc<dyn Fn(Vec<K>) -> String>,
+    ) -> Self {
+        Self::Custom(tokenize_fn, detokenize_fn)
+    }
+}
+
+impl<K: 'static> Tokenizer<K> {
     /// Breaks a `String` into pieces based on `Tokenizer` type.
     ///
-    /// * A `Tokenizer::Slice(length)` will split the String by `length`.
+    /// * A `Tokenizer::Slice(length)` will split the String into byte-window chunks of at most `length` bytes (a single grapheme cluster longer than that is its own chunk).
+    /// * A `Tokenizer::Grapheme(length)` will split the String into chunks of `length` grapheme clusters (the last chunk may be shorter).
     /// * A `Tokenizer::Delimiter(characters)` will split the String by `characters`.
     ///
     /// Arguments
     /// `key` - A `String` that you want to be broken into pieces.
     ///
     /// Returns
-    /// `Vec<String>`
-    pub fn tokenize(&self, key: String) -> Vec<String> {
+    /// `Vec<K>`
+    pub fn tokenize(&self, key: String) -> Vec<K> {
+        if let Self::Custom(tokenize_fn, _) = self {
+            return tokenize_fn(key);
+        }
+        self.tokenize_iter(&key).collect()
+    }
+
+    /// Lazily tokenizes `key`, yielding one `K` at a time instead of
+    /// collecting a `Vec<K>` up front.
+    ///
+    /// `Slice`/`Grapheme`/`Delimiter` walk `key` as borrowed `Cow<str>`
+    /// windows and only turn a window into an owned `K` once it's actually
+    /// produced; `Custom` has no borrowed view of `key` to offer, so it
+    /// still runs `tokenize_fn` eagerly, same as `tokenize`. `add`/`get`/
+    /// `exists` descend the `Trie` off this iterator so a failed lookup
+    /// stops tokenizing the rest of `key` instead of paying for tokens it
+    /// will never use.
+    ///
+    /// Arguments
+    /// `key` - A `&str` that you want to be broken into pieces.
+    ///
+    /// Returns
+    /// `TokenIter<K>`
+    pub fn tokenize_iter<'a>(&'a self, key: &'a str) -> TokenIter<'a, K> {
         match self {
             Self::Slice(length) => {
-                let mut slices = Vec::new();
-                let mut current_slice = String::new();
-
-                for grapheme in key.graphemes(true) {
-                    if current_slice.len() + grapheme.len() <= *length {
-                        current_slice.push_str(grapheme);
-                    } else {
-                        slices.push(current_slice.clone());
-                        current_slice.clear();
-                        current_slice.push_str(grapheme);
-                    }
-                }
-
-                if !current_slice.is_empty() {
-                    slices.push(current_slice);
-                }
-                slices
+                Self::assert_string_tokens();
+                TokenIter::Slice(SliceTokens::new(key, *length))
+            }
+            Self::Grapheme(length) => {
+                Self::assert_string_tokens();
+                TokenIter::Grapheme(GraphemeTokens::new(key, *length))
             }
             Self::Delimiter(delimiter) => {
-                key.split(delimiter).map(|s| s.to_string()).collect()
+                Self::assert_string_tokens();
+                TokenIter::Delimiter(key.split(delimiter.as_str()))
             }
-            Self::Custom(tokenize_fn, _) => tokenize_fn(key)
+            Self::Custom(tokenize_fn, _) => TokenIter::Custom(tokenize_fn(key.to_string()).into_iter()),
         }
     }
 
     /// Joins pieces of a `String` together based on `Tokenizer` type.
     ///
-    /// * A `Tokenizer::Slice` will join elements together without a delimiter.
+    /// * A `Tokenizer::Slice` or `Tokenizer::Grapheme` will join elements together without a delimiter.
     /// * A `Tokenizer::Delimiter` will join elements together with a delimiter.
+    ///
     /// Arguments
-    /// `tokens` - A `Vec<String>` that you'd like to be a single String.
+    /// `tokens` - A `Vec<K>` that you'd like to be a single String.
     ///
     /// Returns
     /// `String`
-    pub fn detokenize(&self, tokens: Vec<String>) -> String {
+    pub fn detokenize(&self, tokens: Vec<K>) -> String {
+        match self {
+            Self::Slice(_) | Self::Grapheme(_) => Self::as_strings(tokens).join(""),
+            Self::Delimiter(delimiter) => Self::as_strings(tokens).join(delimiter),
+            Self::Custom(_, detokenize_fn) => detokenize_fn(tokens)
+        }
+    }
+
+    /// Confirms once that `K` really is `String`, the only `K` `Slice`/
+    /// `Grapheme`/`Delimiter` support.
+    ///
+    /// `Tokenizer::Slice`/`Grapheme`/`Delimiter` can only ever be built
+    /// through `impl Tokenizer<String>` (`slice`/`grapheme`/`delimiter`
+    /// above), so `K` is always `String` by the time a `Tokenizer<K>` value
+    /// holding one of them reaches here; `K` staying a type parameter on
+    /// this whole `impl` block (so `Custom` keeps working for any `K`, see
+    /// `custom_tokenizer_supports_a_non_string_token_type`) is what stops
+    /// the compiler from proving that itself. Checking once here, before
+    /// any token is produced, means a future caller who reaches for
+    /// `Tokenizer::Slice`/`Grapheme`/`Delimiter` directly for some other
+    /// `K` fails immediately and loudly, instead of the old per-token
+    /// `unwrap_or_else(panic!(..))` in `TokenIter::next`/`as_strings`,
+    /// which only blew up on whichever token happened to run the check.
+    fn assert_string_tokens() {
+        assert_eq!(
+            TypeId::of::<K>(),
+            TypeId::of::<String>(),
+            "Tokenizer::Slice/Grapheme/Delimiter only support Trie<T, String>; use Tokenizer::Custom for other token types"
+        );
+    }
+
+    /// The inverse of `token_from_cow`, used by `detokenize` to turn the
+    /// `Vec<K>` `Slice`/`Grapheme`/`Delimiter` produced back into
+    /// `Vec<String>`. Only reached once `assert_string_tokens` has already
+    /// confirmed `K = String`, so the downcast below is infallible.
+    fn as_strings(tokens: Vec<K>) -> Vec<String> {
+        Self::assert_string_tokens();
+        let boxed: Box<dyn Any> = Box::new(tokens);
+        *boxed.downcast::<Vec<String>>().expect("assert_string_tokens just confirmed K == String")
+    }
+}
+
+/// Reinterprets a single token produced by `Slice`/`Grapheme`/`Delimiter`
+/// as a `K`. Only reached once `Tokenizer::tokenize_iter`'s
+/// `assert_string_tokens` has already confirmed `K = String` for this
+/// iterator, so the downcast below is infallible; it isn't re-proven per
+/// token, only the conversion itself runs per token.
+fn token_from_cow<K: 'static>(token: Cow<'_, str>) -> K {
+    let boxed: Box<dyn Any> = Box::new(token.into_owned());
+    *boxed.downcast::<K>().expect("Tokenizer::tokenize_iter already confirmed K == String")
+}
+
+/// The iterator returned by `Tokenizer::tokenize_iter`.
+pub enum TokenIter<'a, K> {
+    Slice(SliceTokens<'a>),
+    Grapheme(GraphemeTokens<'a>),
+    Delimiter(std::str::Split<'a, &'a str>),
+    Custom(std::vec::IntoIter<K>),
+}
+
+impl<'a, K: 'static> Iterator for TokenIter<'a, K> {
+    type Item = K;
+
+    fn next(&mut self) -> Option<K> {
         match self {
-            Self::Slice(_) => {
-                tokens.join("")
+            Self::Slice(tokens) => tokens.next().map(token_from_cow),
+            Self::Grapheme(tokens) => tokens.next().map(token_from_cow),
+            Self::Delimiter(tokens) => tokens.next().map(|s| token_from_cow(Cow::Borrowed(s))),
+            Self::Custom(tokens) => tokens.next(),
+        }
+    }
+}
+
+/// Streams `Tokenizer::Slice`'s byte-window chunks directly out of the
+/// input `&str`, one grapheme cluster at a time, without collecting an
+/// intermediate `Vec`.
+///
+/// Mirrors the chunking rule a `Vec`-collecting implementation would use:
+/// a grapheme is appended to the current window while it still fits in
+/// `length` bytes; once it wouldn't fit, the current window is yielded
+/// and a new window is started with that grapheme. If the current window
+/// is empty (a single grapheme alone already exceeds `length`), nothing
+/// is yielded for it — the oversized grapheme just becomes its own
+/// window on the next iteration, so no spurious empty token is produced.
+pub struct SliceTokens<'a> {
+    key: &'a str,
+    graphemes: std::iter::Peekable<GraphemeIndices<'a>>,
+    length: usize,
+    window_start: usize,
+    window_len: usize,
+    done: bool,
+}
+
+impl<'a> SliceTokens<'a> {
+    fn new(key: &'a str, length: usize) -> Self {
+        Self {
+            key,
+            graphemes: key.grapheme_indices(true).peekable(),
+            length,
+            window_start: 0,
+            window_len: 0,
+            done: false,
+        }
+    }
+}
+
+impl<'a> Iterator for SliceTokens<'a> {
+    type Item = Cow<'a, str>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        loop {
+            match self.graphemes.peek() {
+                Some(&(_, grapheme)) if self.window_len + grapheme.len() <= self.length => {
+                    self.window_len += grapheme.len();
+                    self.graphemes.next();
+                }
+                Some(&(offset, grapheme)) => {
+                    let window = &self.key[self.window_start..self.window_start + self.window_len];
+                    let emit = self.window_len > 0;
+                    self.window_start = offset;
+                    self.window_len = grapheme.len();
+                    self.graphemes.next();
+                    if emit {
+                        return Some(Cow::Borrowed(window));
+                    }
+                }
+                None => {
+                    self.done = true;
+                    if self.window_len > 0 {
+                        return Some(Cow::Borrowed(
+                            &self.key[self.window_start..self.window_start + self.window_len],
+                        ));
+                    }
+                    return None;
+                }
             }
-            Self::Delimiter(delimiter) => {
-                tokens.join(delimiter)
+        }
+    }
+}
+
+/// Streams `Tokenizer::Grapheme`'s fixed-size chunks directly out of the
+/// input `&str`, `length` grapheme clusters at a time, without collecting
+/// an intermediate `Vec`.
+pub struct GraphemeTokens<'a> {
+    key: &'a str,
+    graphemes: std::iter::Peekable<GraphemeIndices<'a>>,
+    length: usize,
+}
+
+impl<'a> GraphemeTokens<'a> {
+    fn new(key: &'a str, length: usize) -> Self {
+        Self {
+            key,
+            graphemes: key.grapheme_indices(true).peekable(),
+            length: length.max(1),
+        }
+    }
+}
+
+impl<'a> Iterator for GraphemeTokens<'a> {
+    type Item = Cow<'a, str>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (start, first) = self.graphemes.next()?;
+        let mut end = start + first.len();
+        for _ in 1..self.length {
+            match self.graphemes.peek() {
+                Some(&(offset, grapheme)) => {
+                    end = offset + grapheme.len();
+                    self.graphemes.next();
+                }
+                None => break,
             }
-            Self::Custom(_, detokenize_fn) => detokenize_fn(tokens)
         }
+        Some(Cow::Borrowed(&self.key[start..end]))
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn grapheme_tokenizer_splits_by_glyph_count_not_byte_length() {
+        let tokenizer = Tokenizer::<String>::grapheme(1);
+        let tokens = tokenizer.tokenize("a🎉é".to_string());
+        assert_eq!(tokens, vec!["a".to_string(), "🎉".to_string(), "é".to_string()]);
+    }
+
+    #[test]
+    fn grapheme_tokenizer_round_trips_a_multibyte_key() {
+        let tokenizer = Tokenizer::<String>::grapheme(2);
+        let key = "a🎉é".to_string();
+        let tokens = tokenizer.tokenize(key.clone());
+        assert_eq!(tokenizer.detokenize(tokens), key);
+    }
+
+    #[test]
+    fn slice_tokenizer_never_splits_a_multibyte_grapheme_across_a_window() {
+        let tokenizer = Tokenizer::<String>::slice(3);
+        let tokens = tokenizer.tokenize("éé".to_string());
+        assert_eq!(tokens, vec!["é".to_string(), "é".to_string()]);
+    }
+
+    #[test]
+    fn slice_tokenizer_emits_no_empty_token_for_a_leading_oversized_grapheme() {
+        let tokenizer = Tokenizer::<String>::slice(1);
+        let tokens = tokenizer.tokenize("🎉bc".to_string());
+        assert_eq!(tokens, vec!["🎉".to_string(), "b".to_string(), "c".to_string()]);
+    }
+}
@@ -1,23 +1,49 @@
-use std::{collections::HashMap, sync::Arc};
+use std::{collections::HashMap, fmt, hash::Hash, sync::Arc};
 
 use super::tokenizer::Tokenizer;
 
-pub struct Trie<T> {
+pub struct Trie<T, K = String> {
     count: u64,
-    pub children: HashMap<String, Trie<T>>,
-    pub data: Option<T>,
+    pub children: HashMap<K, Arc<Trie<T, K>>>,
+    pub data: Option<Arc<T>>,
     is_key_end: bool,
-    tokenizer: Tokenizer,
+    tokenizer: Tokenizer<K>,
 }
 
-impl<T> Default for Trie<T> {
+/// Manual `Clone` impl so that `Trie<T, K>` is cheap to clone (and therefore
+/// cheap to `snapshot`) regardless of whether `T` implements `Clone`. Only
+/// `K: Clone` is required, to clone the child map's keys; children and
+/// stored data are reached through `Arc`, so cloning a `Trie` only bumps
+/// reference counts for the subtrees it shares rather than deep-copying
+/// them.
+impl<T, K: Clone> Clone for Trie<T, K> {
+    fn clone(&self) -> Self {
+        Self {
+            count: self.count,
+            children: self.children.clone(),
+            data: self.data.clone(),
+            is_key_end: self.is_key_end,
+            tokenizer: self.tokenizer.clone(),
+        }
+    }
+}
+
+/// Only implemented for `K = String`: the default `tokenizer`,
+/// `Tokenizer::Slice(1)`, only works for `String` tokens (see
+/// `Tokenizer`'s doc comment). A blanket `impl<T, K> Default for Trie<T, K>`
+/// would let `Trie::<T, MyEnum>::default()` type-check and then panic the
+/// first time `add`/`get` tokenized a key; restricting the `impl` to
+/// `String` catches that mismatch at compile time instead. Use
+/// `Trie::with_custom_tokenization` to build a `Trie` with a non-`String`
+/// `K`.
+impl<T> Default for Trie<T, String> {
     fn default() -> Self {
         Self {
             children: HashMap::new(),
             count: 0,
             data: None,
             is_key_end: false,
-            tokenizer: Tokenizer::Slice(1)
+            tokenizer: Tokenizer::slice(1)
         }
     }
 }
@@ -27,18 +53,40 @@ impl<T> Trie<T> {
     ///
     /// Arguments
     ///
-    /// `length` - A `usize` that represents the length in utf8 glyphs to split the key into.
+    /// `length` - A `usize` that represents the byte budget to split the key into; see
+    /// `Tokenizer::Slice` for the exact chunking rules. Use `with_graphemes` if you want
+    /// chunks of an exact glyph count instead.
     ///
     /// Returns
     ///
-    /// `Trie<T>` - A `Trie<T>` configured to split given keys into `length` glyphs during operations.
+    /// `Trie<T>` - A `Trie<T>` configured to split given keys into `length`-byte chunks during operations.
     pub fn with_slice(length: usize) -> Self {
         Self {
             children: HashMap::new(),
             count: 0,
             data: None,
             is_key_end: false,
-            tokenizer: Tokenizer::Slice(length)
+            tokenizer: Tokenizer::slice(length)
+        }
+    }
+
+    /// Creates a new `Trie<T>` that has a `Tokenizer::Grapheme` set to `usize` `length`.
+    ///
+    /// Arguments
+    ///
+    /// `length` - A `usize` that represents the number of Unicode grapheme clusters to
+    /// split the key into.
+    ///
+    /// Returns
+    ///
+    /// `Trie<T>` - A `Trie<T>` configured to split given keys into `length`-grapheme chunks during operations.
+    pub fn with_graphemes(length: usize) -> Self {
+        Self {
+            children: HashMap::new(),
+            count: 0,
+            data: None,
+            is_key_end: false,
+            tokenizer: Tokenizer::grapheme(length)
         }
     }
 
@@ -59,79 +107,122 @@ impl<T> Trie<T> {
             count: 0,
             data: None,
             is_key_end: false,
-            tokenizer: Tokenizer::Delimiter(delimiter)
+            tokenizer: Tokenizer::delimiter(delimiter)
         }
     }
+}
 
-    /// Creates a new `Trie<T>` that has a `Tokenizer::Custom` which the library
+impl<T, K> Trie<T, K> {
+    /// Creates a new `Trie<T, K>` that has a `Tokenizer::Custom` which the library
     /// user specifies their own tokenize and detokenize functions.
     ///
     /// Arguments
     ///
-    /// `tokenize_fn` - `Arc<dyn Fn(String) -> Vec<String>>`, a function that takes in a `String` and returns a `Vec<String>`, wrapped in an `Arc`. This function is run on each key operation to split keys into different `Trie` levels.
-    /// `detokenize_fn` - `Arc<dyn Fn(Vec<String>) -> String>`, a function that takes in a `Vec<String>`, wrapped in an `Arc`. This function is run to reassemble `Trie` levels into keys.
+    /// `tokenize_fn` - `Arc<dyn Fn(String) -> Vec<K>>`, a function that takes in a `String` and returns a `Vec<K>`, wrapped in an `Arc`. This function is run on each key operation to split keys into different `Trie` levels.
+    /// `detokenize_fn` - `Arc<dyn Fn(Vec<K>) -> String>`, a function that takes in a `Vec<K>`, wrapped in an `Arc`. This function is run to reassemble `Trie` levels into keys.
     ///
     /// Returns
     ///
-    /// `Trie<T>` - A new `Trie` with `tokenizer` set to `Tokenizer::Custom`.
+    /// `Trie<T, K>` - A new `Trie` with `tokenizer` set to `Tokenizer::Custom`.
     pub fn with_custom_tokenization(
-        tokenize_fn: Arc<dyn Fn(String) -> Vec<String>>,
-        detokenize_fn: Arc<dyn Fn(Vec<String>) -> String>
+        tokenize_fn: Arc<dyn Fn(String) -> Vec<K>>,
+        detokenize_fn: Arc<dyn Fn(Vec<K>) -> String>
     ) -> Self {
         Self {
             children: HashMap::new(),
             count: 0,
             data: None,
             is_key_end: false,
-            tokenizer: Tokenizer::Custom(
+            tokenizer: Tokenizer::custom(
                 tokenize_fn.clone(),
                 detokenize_fn.clone()
             )
         }
     }
 
-    /// Creates a new nearly blank `Trie<T>`, clones the `tokenizer` field from the original `Trie<T>`.
+    /// Creates a new nearly blank `Trie<T, K>`, clones the `tokenizer` field from the original `Trie<T, K>`.
     ///
     /// Returns
     ///
-    /// `Trie<T>`
+    /// `Trie<T, K>`
     pub fn new_from_current(&self) -> Self {
-        let tokenizer = match &self.tokenizer {
-            Tokenizer::Slice(length) => Tokenizer::Slice(*length),
-            Tokenizer::Delimiter(delimiter) => Tokenizer::Delimiter(delimiter.clone()),
-            Tokenizer::Custom(tokenize_fn, detokenize_fn) => Tokenizer::Custom(
-                Arc::clone(tokenize_fn),
-                Arc::clone(detokenize_fn)
-            )
-        };
         Self {
             children: HashMap::new(),
             count: 0,
             data: None,
             is_key_end: false,
-            tokenizer
+            tokenizer: self.tokenizer.clone()
         }
     }
+}
+
+impl<T, K: Hash + Eq + Clone + 'static> Trie<T, K> {
+    /// Returns a cheap, independent copy of this `Trie`.
+    ///
+    /// The returned `Trie` shares all of its children with `self` through
+    /// `Arc`, so taking a snapshot does not walk or duplicate the tree.
+    /// Subsequent calls to `add`/`remove` on either the snapshot or the
+    /// original only clone the nodes along the path they touch (via
+    /// copy-on-write), leaving the other's view of shared subtrees intact.
+    ///
+    /// Returns
+    ///
+    /// `Trie<T, K>` - An independent handle onto the same data, safe to keep
+    /// around as a historical version while `self` continues to mutate.
+    pub fn snapshot(&self) -> Trie<T, K> {
+        self.clone()
+    }
+
     /// Adds a complete `key` to the `Trie` structure.
     ///
+    /// Tokenizes `key` lazily through `Tokenizer::tokenize_iter` instead of
+    /// collecting a `Vec<K>` up front. The tokenizer itself is cloned
+    /// (cheap, see `Tokenizer`'s `Clone` impl) so the iterator doesn't hold
+    /// a borrow of `self` while the loop below mutates it.
+    ///
     /// Arguments
     ///
     /// `key` - A `&str` which is a complete key.
     /// `data` - An `Option<T>` which is stored in the Trie at the end of the key.
     pub fn add(&mut self, key: &str, data: Option<T>) {
-        let keystr = String::from(key);
+        self.add_and_get_mut(key, data);
+    }
+
+    /// The guts of `add`, also used by `VacantEntry::insert` so inserting
+    /// through an `Entry` can hand back a `&mut T` without a second
+    /// descent to re-find the node `add` just created.
+    fn add_and_get_mut(&mut self, key: &str, data: Option<T>) -> &mut Trie<T, K> {
+        let tokenizer = self.tokenizer.clone();
         let mut trie = self;
-        for token in trie.tokenizer.tokenize(keystr) {
+        for token in tokenizer.tokenize_iter(key) {
             let new_child = trie.new_from_current();
-            trie = trie.children.entry(token).or_insert_with(|| new_child);
+            let child_arc = trie.children.entry(token).or_insert_with(|| Arc::new(new_child));
+            trie = Arc::make_mut(child_arc);
             trie.count += 1;
         }
-        trie.data = data;
+        trie.data = data.map(Arc::new);
         trie.is_key_end = true;
+        trie
     }
 
     /// Removes a `key` from the `Trie` structure.
     ///
+    /// Returns early unless `key` is a stored key (checked with `exists`).
+    /// Otherwise tokenizes `key` into a `Vec<K>`, then walks down to the
+    /// matching node by recursing directly on `&mut Trie<T, K>` in a single
+    /// downward pass.
+    /// Counts are decremented and emptied children pruned on the way back
+    /// up, as the recursion unwinds, instead of re-tokenizing and
+    /// re-walking from the root once per token.
+    ///
+    /// This recurses on `&mut Trie<T, K>` rather than `CursorMut` on
+    /// purpose: `CursorMut::descend` consumes the cursor and returns a new
+    /// one positioned on the child, with no way back to the parent, so it
+    /// can't prune the parent's children once the child call returns. A
+    /// plain `&mut` borrow of the child keeps the parent's stack frame
+    /// (and its `children` map) reachable for pruning after the recursive
+    /// call unwinds.
+    ///
     /// Arguments
     ///
     /// `key` - A `&str` that you're removing from the Trie
@@ -139,31 +230,57 @@ impl<T> Trie<T> {
         if !self.exists(key) {
             return;
         }
-        let mut tokens = self.tokenizer.tokenize(String::from(key));
-        let mut is_first = true;
-        while tokens.len() > 0 {
-            let detokenized = self.tokenizer.detokenize(tokens.clone());
-            let token = detokenized.as_str();
-            if let Some(trie) = self.get_mut(token) {
-                trie.count -= 1;
-                if is_first {
-                    is_first = false;
-                    trie.is_key_end = false;
-                }
+        let tokens = self.tokenizer.tokenize(String::from(key));
+        Self::remove_along(self, &tokens);
+    }
 
-                trie.prune_unused_children();
-            } else {
-                return;
-            }
-            tokens.pop();
+    /// Descends `node` through `tokens` one at a time. Once the deepest
+    /// token is reached its node's `is_key_end` is cleared; as each
+    /// recursive call returns, the child it descended into has its `count`
+    /// decremented and `node` prunes its own now-empty children before
+    /// returning to its caller, so both happen on the way back up from the
+    /// leaf to the root.
+    fn remove_along(node: &mut Trie<T, K>, tokens: &[K]) {
+        let Some((token, rest)) = tokens.split_first() else {
+            return;
+        };
+        let Some(child_arc) = node.children.get_mut(token) else {
+            return;
+        };
+
+        let child = Arc::make_mut(child_arc);
+        if rest.is_empty() {
+            child.is_key_end = false;
         }
+        Self::remove_along(child, rest);
+        child.count -= 1;
+        node.prune_unused_children();
+    }
+
+    /// Returns a read-only `Cursor` positioned at this `Trie`'s root.
+    ///
+    /// A `Cursor` holds the path from the root down to its current
+    /// position so repeated, incremental descent (e.g. one token per
+    /// keystroke while autocompleting) never re-walks from the root.
+    pub fn cursor(&self) -> Cursor<'_, T, K> {
+        Cursor::new(self)
+    }
 
-        self.prune_unused_children();
+    /// Returns a mutable, copy-on-write `CursorMut` positioned at this
+    /// `Trie`'s root.
+    ///
+    /// Unlike `Cursor`, descending consumes the cursor and returns a new
+    /// one deeper in the tree, since only one mutable path down the tree
+    /// can be held at a time. Each descent runs through `Arc::make_mut`,
+    /// so it participates in the same copy-on-write semantics as `add`
+    /// and `remove`.
+    pub fn cursor_mut(&mut self) -> CursorMut<'_, T, K> {
+        CursorMut::new(self)
     }
 
     /// Removes all children from the `Trie` that have a 0 count.
     fn prune_unused_children(&mut self) {
-        let unused_children: Vec<String> = self.children
+        let unused_children: Vec<K> = self.children
             .iter()
             .filter(|(_, v)| v.count == 0)
             .map(|(k, _)| k.clone())
@@ -187,49 +304,55 @@ impl<T> Trie<T> {
         false
     }
 
+    /// Whether this node is the end of a key that was `add`ed to the
+    /// `Trie`, as opposed to merely a prefix on the way to one.
+    ///
+    /// Note this is `true` for a key added via `add(key, None)`, even
+    /// though `data` is `None` for that node; use this together with
+    /// `data` rather than `data.is_some()` alone to tell "no value" apart
+    /// from "not a stored key".
+    pub fn is_key_end(&self) -> bool {
+        self.is_key_end
+    }
+
     /// Get an immutable Trie from the Trie queried.
     ///
     /// Arguments:
     /// `key` - A `&str` representing the full path to the Trie you're querying.
     ///
     /// Returns:
-    /// `Option<&Trie<T>>`
+    /// `Option<&Trie<T, K>>`
     /// * When the `key` doesn't exist in Trie's children, `None` is returned.
-    /// * When the `key` exists in the Trie's children, the last child will be returned as
-    /// `Some(&Trie<T>)`
-    pub fn get(&self, key: &str) -> Option<&Trie<T>> {
+    /// * When the `key` exists in the Trie's children, the last child will be returned as `Some(&Trie<T, K>)`
+    pub fn get(&self, key: &str) -> Option<&Trie<T, K>> {
         let mut trie = self;
-        let tokens = self.tokenizer.tokenize(String::from(key));
-        let mut iter = tokens.iter();
-
-        while let Some(token) = iter.next() {
-            if let Some(t) = trie.children.get(token) {
-                trie = t;
-            } else {
-                return None;
-            }
+        for token in self.tokenizer.tokenize_iter(key) {
+            trie = trie.children.get(&token)?.as_ref();
         }
         Some(trie)
     }
 
     /// Get a mutable Trie from the Trie queried.
     ///
+    /// Walks from `self` down to the node addressed by `key`, copy-on-write:
+    /// each node along the path is obtained through `Arc::make_mut`, so a
+    /// node shared with another `snapshot` is cloned before being handed
+    /// back as mutable, while untouched siblings keep sharing their `Arc`.
+    ///
     /// Arguments:
     /// `key` - A `&str` representing the full path to the Trie you're querying.
     ///
     /// Returns:
-    /// `Option<&mut Trie<T>>`
+    /// `Option<&mut Trie<T, K>>`
     /// * When the `key` doesn't exist in Trie's children, `None` is returned.
-    /// * When the `key` exists in the Trie's children, the last child will be returned as
-    /// `Some(&mut Trie<T>)`
-    pub fn get_mut(&mut self, key: &str) -> Option<&mut Trie<T>> {
+    /// * When the `key` exists in the Trie's children, the last child will be returned as `Some(&mut Trie<T, K>)`
+    pub fn get_mut(&mut self, key: &str) -> Option<&mut Trie<T, K>> {
+        let tokenizer = self.tokenizer.clone();
         let mut trie = self;
-        let tokens = trie.tokenizer.tokenize(String::from(key));
-        let mut iter = tokens.iter();
 
-        while let Some(token) = iter.next() {
-            if let Some(t) = trie.children.get_mut(token) {
-                trie = t;
+        for token in tokenizer.tokenize_iter(key) {
+            if let Some(t) = trie.children.get_mut(&token) {
+                trie = Arc::make_mut(t);
             } else {
                 return None;
             }
@@ -238,6 +361,138 @@ impl<T> Trie<T> {
         Some(trie)
     }
 
+    /// Iterates over every key/value pair stored in the `Trie`.
+    ///
+    /// Each `is_key_end` descendant with `data` set contributes one pair:
+    /// its token path reconstructed into a `String` via `detokenize`,
+    /// alongside a reference to the `data` stored there. Keys added with no
+    /// data (`add(key, None)`) are not yielded. Order is unspecified, since
+    /// it follows the `HashMap` iteration order of `children` at each level.
+    ///
+    /// Returns
+    ///
+    /// `impl Iterator<Item = (String, &T)>`
+    pub fn iter(&self) -> impl Iterator<Item = (String, &T)> {
+        let mut pairs = Vec::new();
+        self.iter_recursive(Vec::new(), &mut pairs);
+        pairs.into_iter()
+    }
+
+    /// Collects the key/token path down to every `is_key_end` descendant of
+    /// this `Trie` that has `data` set, reconstructing each as a `String` via `detokenize` and
+    /// pairing it with a reference to the stored `data`.
+    fn iter_recursive<'a>(&'a self, prefix: Vec<K>, pairs: &mut Vec<(String, &'a T)>) {
+        if let (true, Some(data)) = (self.is_key_end, &self.data) {
+            pairs.push((self.tokenizer.detokenize(prefix.clone()), data.as_ref()));
+        }
+        for (token, trie) in &self.children {
+            let mut next = prefix.clone();
+            next.push(token.clone());
+            trie.iter_recursive(next, pairs);
+        }
+    }
+
+    /// Whether this subtree contains at least one `is_key_end` node with
+    /// `data` set, i.e. whether descending into it could ever yield a pair
+    /// from `iter`/`iter_mut`.
+    ///
+    /// Purely a read: used by `iter_mut_recursive` to decide which children
+    /// are worth an `Arc::make_mut` before descending, so subtrees that hold
+    /// no data at all (e.g. prefixes added via `add(key, None)`) are never
+    /// needlessly cloned out from under a live `snapshot`.
+    fn has_data_below(&self) -> bool {
+        (self.is_key_end && self.data.is_some())
+            || self.children.values().any(|child| child.has_data_below())
+    }
+}
+
+impl<T: Clone, K: Hash + Eq + Clone + 'static> Trie<T, K> {
+    /// Iterates over every key/value pair stored in the `Trie`, with a
+    /// mutable reference to each value.
+    ///
+    /// Mirrors `iter`, but each node's `data` is reached through
+    /// `Arc::make_mut`, so a node shared with another `snapshot` is cloned
+    /// the moment its value is handed back as mutable, same as `get_mut`.
+    ///
+    /// Returns
+    ///
+    /// `impl Iterator<Item = (String, &mut T)>`
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (String, &mut T)> {
+        let mut pairs = Vec::new();
+        self.iter_mut_recursive(Vec::new(), &mut pairs);
+        pairs.into_iter()
+    }
+
+    /// Copy-on-write counterpart to `iter_recursive`: descends through
+    /// `Arc::make_mut` on both children and `data`.
+    ///
+    /// Skips children whose subtree has no data at all (`has_data_below`),
+    /// so a no-data chain shared with another `snapshot` is left untouched
+    /// instead of being deep-cloned just because it was on the way to a
+    /// sibling that does hold data.
+    fn iter_mut_recursive<'a>(&'a mut self, prefix: Vec<K>, pairs: &mut Vec<(String, &'a mut T)>) {
+        if let (true, Some(data)) = (self.is_key_end, self.data.as_mut()) {
+            pairs.push((self.tokenizer.detokenize(prefix.clone()), Arc::make_mut(data)));
+        }
+        for (token, trie) in self.children.iter_mut() {
+            if !trie.has_data_below() {
+                continue;
+            }
+            let mut next = prefix.clone();
+            next.push(token.clone());
+            Arc::make_mut(trie).iter_mut_recursive(next, pairs);
+        }
+    }
+
+    /// Returns the given `key`'s `Entry` into the `Trie`, for in-place
+    /// insertion or update.
+    ///
+    /// Mirrors `HashMap::entry`: an `Entry::Occupied` means the key already
+    /// resolves to an `is_key_end` node with `data` set, while
+    /// `Entry::Vacant` covers everything else — no node at all, a node
+    /// that isn't `is_key_end`, or (notably) a node added via
+    /// `add(key, None)`, which is `is_key_end` but has no `data`. `Vacant`
+    /// defers creating or overwriting any nodes until
+    /// `insert`/`or_insert_with` is actually called, so merely inspecting
+    /// a missing key never leaves behind empty intermediate nodes.
+    ///
+    /// Locating the key here only ever borrows `self` immutably (the same
+    /// descent `get` already does), so plain reads through the resulting
+    /// `Entry` — `OccupiedEntry::get` — never trigger the `Arc::make_mut`
+    /// copy-on-write clones that a mutable descent would. Those only
+    /// happen if `get_mut`/`into_mut`/`insert`/`or_insert_with` is
+    /// actually called, each doing exactly one such descent.
+    ///
+    /// Arguments
+    ///
+    /// `key` - A `&str` which is a complete key.
+    ///
+    /// Returns
+    ///
+    /// `Entry<'_, T, K>`
+    pub fn entry(&mut self, key: &str) -> Entry<'_, T, K> {
+        let occupied = self.get(key).is_some_and(|node| node.is_key_end && node.data.is_some());
+        let key = key.to_string();
+        if occupied {
+            Entry::Occupied(OccupiedEntry { trie: self, key })
+        } else {
+            Entry::Vacant(VacantEntry { trie: self, key })
+        }
+    }
+}
+
+// Known gap: `fuzzy_get`, `get_keys_by_partial_path`, and
+// `get_keys_under_prefix` below are still scoped to `impl<T> Trie<T>`
+// (i.e. `K = String`) rather than the generic `K` the rest of `Trie`
+// supports as of this Trie/Tokenizer genericization. `fuzzy_get` and
+// `get_keys_by_partial_path` compare and concatenate tokens as
+// `&str`/`String` directly (`k.contains(token.as_str())`,
+// `String::from(k)`), which only makes sense for `K = String`, and
+// `get_keys_under_prefix` shares their `impl` block; lifting
+// that restriction would need a generic way to test one token against a
+// fuzzy substring, which `K` doesn't offer. Left String-only rather than
+// genericized along with the rest of the API.
+impl<T> Trie<T> {
     /// Gets one or more Tries from the Trie queried, and returns it in vector.
     ///
     /// Arguments:
@@ -253,11 +508,11 @@ impl<T> Trie<T> {
         let mut trie = self;
         let mut tokens = self.tokenizer.tokenize(String::from(key));
         let last_token = tokens.pop();
-        let mut iter = tokens.iter();
+        let iter = tokens.iter();
         let mut items: Vec<&Trie<T>> = Vec::new();
-        while let Some(token) = iter.next() {
+        for token in iter {
             if let Some(t) = trie.children.get(token) {
-                trie = t;
+                trie = t.as_ref();
             } else {
                 return items;
             }
@@ -279,16 +534,16 @@ impl<T> Trie<T> {
         let mut trie = self;
         let mut tokens = self.tokenizer.tokenize(String::from(key));
         let last_token = tokens.pop().unwrap_or(String::from(""));
-        let mut iter = tokens.iter();
+        let iter = tokens.iter();
         let items: Vec<String> = Vec::new();
-        while let Some(token) = iter.next() {
+        for token in iter {
             if let Some(t) = trie.children.get(token) {
-                trie = t;
+                trie = t.as_ref();
             } else {
                 return items;
             }
         }
-        if let Some(_) = trie.get(last_token.as_str()) {
+        if trie.get(last_token.as_str()).is_some() {
             return vec![String::from(key)];
         }
 
@@ -307,6 +562,10 @@ impl<T> Trie<T> {
 
     /// Collects all keys of children under a given prefix.
     ///
+    /// Walks every `is_key_end` node, including keys added with no data,
+    /// reconstructs its key via `detokenize` and keeps those that start
+    /// with `key`.
+    ///
     /// Arguments:
     /// `key` - A `&str` representing the prefix you're searching under.
     ///
@@ -314,27 +573,435 @@ impl<T> Trie<T> {
     /// `Vec<String>` - A vector of strings containing the collected keys under the prefix.
     pub fn get_keys_under_prefix(&self, key: &str) -> Vec<String> {
         let mut keys = Vec::new();
-        let search_keys = self.get_keys_by_partial_path(key);
-        for k in search_keys {
-            if let Some(trie) = self.get(k.as_str()) {
-                trie.get_keys_recursive(k.as_str(), &mut keys);
+        let mut stack: Vec<(Vec<String>, &Trie<T>)> = vec![(Vec::new(), self)];
+        while let Some((path, node)) = stack.pop() {
+            if node.is_key_end {
+                let k = self.tokenizer.detokenize(path.clone());
+                if k.starts_with(key) { keys.push(k); }
+            }
+            for (token, child) in &node.children {
+                let mut next = path.clone();
+                next.push(token.clone());
+                stack.push((next, child.as_ref()));
             }
         }
         keys
     }
+}
 
-    fn get_keys_recursive(&self, key: &str, keys: &mut Vec<String>) {
-        if self.is_key_end {
-            keys.push(String::from(key));
+/// A read-only cursor into a `Trie`, positioned at some node reachable
+/// from the root it was created from.
+///
+/// A `Cursor` holds the stack of nodes from the root down to its current
+/// position, so repeated incremental descent (e.g. one token per
+/// keystroke while autocompleting) never re-walks from the root the way
+/// `Trie::get` does.
+pub struct Cursor<'a, T, K = String> {
+    stack: Vec<&'a Trie<T, K>>,
+}
+
+impl<'a, T, K: Hash + Eq> Cursor<'a, T, K> {
+    fn new(root: &'a Trie<T, K>) -> Self {
+        Self { stack: vec![root] }
+    }
+
+    /// Attempts to descend into the child addressed by `token`.
+    ///
+    /// Returns `true` and moves the cursor into that child if it exists;
+    /// otherwise leaves the cursor in place and returns `false`.
+    pub fn descend(&mut self, token: &K) -> bool {
+        if let Some(child) = self.current().children.get(token) {
+            self.stack.push(child.as_ref());
+            true
+        } else {
+            false
         }
-        for (token, trie) in &self.children {
-            let new_key = self.tokenizer.detokenize(
-                vec![
-                    String::from(key),
-                    String::from(token)
-                ]
-            );
-            trie.get_keys_recursive(&new_key, keys);
+    }
+
+    /// Moves the cursor back up to the parent of the current node.
+    ///
+    /// Does nothing if the cursor is already positioned at the root it
+    /// was created from.
+    pub fn ascend(&mut self) {
+        if self.stack.len() > 1 {
+            self.stack.pop();
+        }
+    }
+
+    /// The node the cursor is currently positioned on.
+    pub fn current(&self) -> &'a Trie<T, K> {
+        self.stack[self.stack.len() - 1]
+    }
+}
+
+/// Manual `Debug` impl so `Cursor<T, K>` is printable regardless of whether
+/// `T`/`K` implement `Debug` (a `#[derive(Debug)]` here would require both,
+/// since the `Trie` nodes on the stack carry a `Tokenizer<K>`, which can't
+/// derive `Debug` itself because of `Tokenizer::Custom`'s closures).
+impl<'a, T, K> fmt::Debug for Cursor<'a, T, K> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Cursor").field("depth", &self.stack.len()).finish()
+    }
+}
+
+impl<'a, T, K: Hash + Eq + 'static> Cursor<'a, T, K> {
+    /// Tokenizes `key` once with the current node's tokenizer and descends
+    /// one token at a time.
+    ///
+    /// Stops and returns `false` the moment a token has no matching
+    /// child, leaving the cursor at the deepest node it reached.
+    pub fn descend_key(&mut self, key: &str) -> bool {
+        let tokens = self.current().tokenizer.tokenize(String::from(key));
+        for token in &tokens {
+            if !self.descend(token) {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+/// A mutable, copy-on-write cursor into a `Trie`.
+///
+/// Unlike `Cursor`, `CursorMut` can only hold one mutable path down the
+/// tree at a time, so descending consumes the cursor and returns a new
+/// one positioned on the child, rather than moving a shared cursor in
+/// place. Each descent runs the child `Arc` through `Arc::make_mut`, so a
+/// node shared with another `snapshot` is cloned the moment it is
+/// touched, same as `add` and `get_mut`.
+pub struct CursorMut<'a, T, K = String> {
+    node: &'a mut Trie<T, K>,
+    path: Vec<K>,
+}
+
+impl<'a, T, K: Hash + Eq + Clone> CursorMut<'a, T, K> {
+    fn new(node: &'a mut Trie<T, K>) -> Self {
+        Self { node, path: Vec::new() }
+    }
+
+    /// Attempts to descend into the child addressed by `token`,
+    /// copy-on-write.
+    ///
+    /// Returns `Ok` with a cursor positioned on that child if it exists,
+    /// or `Err` with the original, unmoved cursor otherwise.
+    pub fn descend(self, token: &K) -> Result<Self, Self> {
+        let CursorMut { node, mut path } = self;
+        if node.children.contains_key(token) {
+            path.push(token.clone());
+            let child_arc = node.children.get_mut(token).unwrap();
+            Ok(CursorMut { node: Arc::make_mut(child_arc), path })
+        } else {
+            Err(CursorMut { node, path })
+        }
+    }
+
+    /// The node the cursor is currently positioned on.
+    pub fn current(&self) -> &Trie<T, K> {
+        self.node
+    }
+
+    /// Mutable access to the node the cursor is currently positioned on.
+    pub fn current_mut(&mut self) -> &mut Trie<T, K> {
+        self.node
+    }
+
+    /// The tokens consumed to reach the current position, in descent order.
+    pub fn path(&self) -> &[K] {
+        &self.path
+    }
+}
+
+/// Manual `Debug` impl, for the same reason as `Cursor`'s: letting
+/// `descend`'s `Result<Self, Self>` be `.unwrap()`-ed requires `Self: Debug`,
+/// but deriving it would force `T: Debug, K: Debug` on every `CursorMut`
+/// even though the node it points at may not support either.
+impl<'a, T, K> fmt::Debug for CursorMut<'a, T, K> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CursorMut").field("depth", &self.path.len()).finish()
+    }
+}
+
+/// A view into a single key's slot in a `Trie`, returned by `Trie::entry`.
+///
+/// Mirrors `std::collections::hash_map::Entry`: `Occupied` means the key
+/// already resolves to a node with a value, `Vacant` covers everything
+/// else (including a node added via `add(key, None)`) and defers any
+/// mutation until `insert`/`or_insert_with` is actually called.
+pub enum Entry<'a, T, K = String> {
+    Occupied(OccupiedEntry<'a, T, K>),
+    Vacant(VacantEntry<'a, T, K>),
+}
+
+impl<'a, T: Clone, K: Hash + Eq + Clone + 'static> Entry<'a, T, K> {
+    /// Returns a mutable reference to the value in the entry, inserting
+    /// `default()` first if the entry is `Vacant`.
+    pub fn or_insert_with<F: FnOnce() -> T>(self, default: F) -> &'a mut T {
+        match self {
+            Self::Occupied(entry) => entry.into_mut(),
+            Self::Vacant(entry) => entry.insert(default()),
+        }
+    }
+}
+
+/// An occupied `Entry`: `key` already resolves to a node with `data` set.
+///
+/// Holds the `Trie` root rather than the resolved node itself, so `get`
+/// can re-find the node through a plain, read-only `Trie::get` — no
+/// `Arc::make_mut` copy-on-write clone happens unless `get_mut`/
+/// `into_mut`/`insert` is actually called.
+pub struct OccupiedEntry<'a, T, K = String> {
+    trie: &'a mut Trie<T, K>,
+    key: String,
+}
+
+impl<'a, T: Clone, K: Hash + Eq + Clone + 'static> OccupiedEntry<'a, T, K> {
+    /// A reference to the value currently stored at this entry.
+    ///
+    /// Panics if the node has no `data`, which shouldn't happen: `Trie::entry`
+    /// only produces `Occupied` for a key whose node is `is_key_end` with
+    /// `data` already set.
+    pub fn get(&self) -> &T {
+        self.trie.get(&self.key)
+            .and_then(|node| node.data.as_deref())
+            .expect("Occupied entry only constructed when data is present")
+    }
+
+    /// A mutable reference to the value currently stored at this entry,
+    /// borrowing from `self`.
+    ///
+    /// Reaches the value through one copy-on-write descent via `get_mut`,
+    /// so a value shared with another `snapshot` is cloned the moment
+    /// it's handed back as mutable.
+    pub fn get_mut(&mut self) -> &mut T {
+        let node = self.trie.get_mut(&self.key)
+            .expect("Occupied entry's key is reachable");
+        Arc::make_mut(node.data.as_mut().expect("Occupied entry only constructed when data is present"))
+    }
+
+    /// A mutable reference to the value currently stored at this entry,
+    /// borrowing for the lifetime of the underlying `Trie`.
+    pub fn into_mut(self) -> &'a mut T {
+        let node = self.trie.get_mut(&self.key)
+            .expect("Occupied entry's key is reachable");
+        Arc::make_mut(node.data.as_mut().expect("Occupied entry only constructed when data is present"))
+    }
+
+    /// Replaces the stored value, returning the previous one.
+    pub fn insert(&mut self, value: T) -> T {
+        let node = self.trie.get_mut(&self.key)
+            .expect("Occupied entry's key is reachable");
+        let old = node.data.replace(Arc::new(value));
+        Arc::try_unwrap(old.expect("Occupied entry only constructed when data is present"))
+            .unwrap_or_else(|shared| (*shared).clone())
+    }
+}
+
+/// A vacant `Entry`: `key` has no node, resolves to a node that isn't
+/// `is_key_end`, or resolves to an `is_key_end` node with no `data` (e.g.
+/// one added via `add(key, None)`).
+pub struct VacantEntry<'a, T, K> {
+    trie: &'a mut Trie<T, K>,
+    key: String,
+}
+
+impl<'a, T: Clone, K: Hash + Eq + Clone + 'static> VacantEntry<'a, T, K> {
+    /// Inserts `value` at this entry's key, returning a mutable reference to
+    /// it without re-walking to find the node it just touched.
+    ///
+    /// A `Vacant` entry covers two distinct shapes: the key's node may not
+    /// exist yet (or exist but not be `is_key_end`), in which case this
+    /// routes through `add_and_get_mut` to create the path; or the node may
+    /// already be `is_key_end` with `data: None` (e.g. from `add(key,
+    /// None)`), in which case the path already exists and only `data` needs
+    /// setting. Routing the latter through `add_and_get_mut` would
+    /// re-increment every ancestor's `count` for nodes that aren't new,
+    /// leaving `remove` unable to ever bring `count` back to 0.
+    pub fn insert(self, value: T) -> &'a mut T {
+        let already_key_end = self.trie.get(&self.key).is_some_and(|node| node.is_key_end);
+        if already_key_end {
+            let node = self.trie.get_mut(&self.key).expect("just confirmed the key resolves to a node");
+            node.data = Some(Arc::new(value));
+            return Arc::make_mut(node.data.as_mut().expect("just set data"));
+        }
+        let node = self.trie.add_and_get_mut(&self.key, Some(value));
+        Arc::make_mut(node.data.as_mut().expect("add_and_get_mut(key, Some(value)) just set data"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn remove_prunes_only_the_removed_keys_ancestors() {
+        let mut trie: Trie<i32> = Trie::with_slice(1);
+        trie.add("abcd", Some(1));
+        trie.add("abxy", Some(2));
+
+        trie.remove("abcd");
+
+        assert!(!trie.exists("abcd"));
+        assert!(trie.exists("abxy"));
+
+        let ab = trie.get("ab").expect("\"ab\" is still a shared prefix of \"abxy\"");
+        let mut remaining: Vec<&String> = ab.children.keys().collect();
+        remaining.sort();
+        assert_eq!(remaining, vec!["x"], "dead \"c\" branch left behind by removing \"abcd\"");
+    }
+
+    #[test]
+    fn entry_on_key_added_with_no_data_is_vacant() {
+        let mut trie: Trie<i32> = Trie::with_slice(3);
+        trie.add("foo", None);
+
+        assert!(trie.exists("foo"));
+        match trie.entry("foo") {
+            Entry::Occupied(_) => panic!("a key added with data: None must not be Occupied"),
+            Entry::Vacant(_) => {}
         }
+
+        *trie.entry("foo").or_insert_with(|| 0) += 5;
+        match trie.entry("foo") {
+            Entry::Occupied(entry) => assert_eq!(*entry.get(), 5),
+            Entry::Vacant(_) => panic!("\"foo\" should be Occupied after or_insert_with"),
+        }
+    }
+
+    #[test]
+    fn filling_in_data_via_entry_does_not_double_count_an_existing_key() {
+        let mut trie: Trie<i32> = Trie::with_slice(3);
+        trie.add("foo", None);
+        trie.entry("foo").or_insert_with(|| 1);
+
+        trie.remove("foo");
+
+        assert!(!trie.exists("foo"), "a single remove() must undo add() + entry().or_insert_with()");
+        assert!(trie.children.is_empty(), "dead \"foo\" subtree left behind by a double-counted entry() fill-in");
+    }
+
+    #[test]
+    fn iter_mut_leaves_untouched_no_data_subtrees_shared_with_a_snapshot() {
+        let mut trie: Trie<i32> = Trie::with_slice(1);
+        trie.add("bbbbbbbbbb", None);
+        trie.add("a", Some(1));
+
+        let snap = trie.snapshot();
+        let before = Arc::as_ptr(trie.children.get("b").expect("\"b\" is the shared no-data prefix"));
+
+        for (_, value) in trie.iter_mut() {
+            *value += 1;
+        }
+
+        let after = Arc::as_ptr(trie.children.get("b").expect("\"b\" must still be present"));
+        assert_eq!(before, after, "iter_mut must not clone a subtree with no data at all");
+        assert!(snap.exists("bbbbbbbbbb"), "snapshot should be unaffected regardless");
+    }
+
+    #[test]
+    fn snapshot_is_unaffected_by_later_mutation_of_the_live_trie() {
+        let mut trie: Trie<i32> = Trie::with_slice(1);
+        trie.add("abc", Some(1));
+
+        let snap = trie.snapshot();
+
+        trie.add("abc", Some(2));
+        trie.add("xyz", Some(3));
+        trie.remove("abc");
+
+        assert!(!trie.exists("abc"), "\"abc\" should be gone from the live trie");
+        assert!(trie.exists("xyz"));
+
+        assert!(snap.exists("abc"), "snapshot must still see the key as it was when taken");
+        assert!(!snap.exists("xyz"), "snapshot must not see keys added after it was taken");
+        assert_eq!(
+            snap.get("abc").and_then(|node| node.data.as_deref()),
+            Some(&1),
+            "snapshot's value must not reflect the live trie's later overwrite"
+        );
+    }
+
+    #[test]
+    fn custom_tokenizer_supports_a_non_string_token_type() {
+        let tokenize: Arc<dyn Fn(String) -> Vec<u32>> =
+            Arc::new(|key: String| key.chars().map(|c| c as u32).collect());
+        let detokenize: Arc<dyn Fn(Vec<u32>) -> String> =
+            Arc::new(|tokens: Vec<u32>| tokens.into_iter().filter_map(char::from_u32).collect());
+
+        let mut trie: Trie<i32, u32> = Trie::with_custom_tokenization(tokenize, detokenize);
+        trie.add("ab", Some(1));
+        trie.add("ac", Some(2));
+
+        assert!(trie.exists("ab"));
+        assert_eq!(trie.get("ab").and_then(|node| node.data.as_deref()), Some(&1));
+
+        let mut keys: Vec<String> = trie.iter().map(|(key, _)| key).collect();
+        keys.sort();
+        assert_eq!(keys, vec!["ab".to_string(), "ac".to_string()]);
+    }
+
+    #[test]
+    fn cursor_descend_and_ascend_walk_the_trie_one_token_at_a_time() {
+        let mut trie: Trie<i32> = Trie::with_slice(1);
+        trie.add("ab", Some(1));
+
+        let mut cursor = trie.cursor();
+        assert!(cursor.descend(&"a".to_string()));
+        assert!(!cursor.current().is_key_end());
+        assert!(cursor.descend(&"b".to_string()));
+        assert!(cursor.current().is_key_end());
+
+        assert!(!cursor.descend(&"c".to_string()), "\"abc\" was never added");
+        assert!(cursor.current().is_key_end(), "a failed descend must leave the cursor in place");
+
+        cursor.ascend();
+        assert!(!cursor.current().is_key_end(), "ascend should move back to \"a\"");
+        cursor.ascend();
+        cursor.ascend();
+        assert!(!cursor.current().is_key_end(), "ascend past the root must stay at the root");
+    }
+
+    #[test]
+    fn cursor_descend_key_tokenizes_and_stops_at_the_first_missing_token() {
+        let mut trie: Trie<i32> = Trie::with_slice(1);
+        trie.add("ab", Some(1));
+
+        let mut cursor = trie.cursor();
+        assert!(!cursor.descend_key("abc"), "\"abc\" was never added");
+        assert!(cursor.current().is_key_end(), "should still have descended as far as \"ab\"");
+
+        let mut cursor = trie.cursor();
+        assert!(cursor.descend_key("ab"));
+        assert!(cursor.current().is_key_end());
+    }
+
+    #[test]
+    fn cursor_mut_descend_is_copy_on_write_against_a_snapshot() {
+        let mut trie: Trie<i32> = Trie::with_slice(1);
+        trie.add("ab", Some(1));
+
+        let snap = trie.snapshot();
+        let before = Arc::as_ptr(snap.children.get("a").expect("\"a\" exists in the snapshot"));
+
+        let cursor = trie.cursor_mut();
+        let mut cursor = cursor.descend(&"a".to_string()).expect("\"a\" exists");
+        cursor.current_mut().data = Some(Arc::new(99));
+
+        let after = Arc::as_ptr(snap.children.get("a").expect("snapshot's \"a\" must be untouched"));
+        assert_eq!(before, after, "descending through cursor_mut must not disturb a snapshot's shared node");
+        assert_eq!(trie.get("a").and_then(|node| node.data.as_deref()), Some(&99));
+    }
+
+    #[test]
+    fn cursor_mut_descend_returns_the_cursor_unmoved_on_a_missing_token() {
+        let mut trie: Trie<i32> = Trie::with_slice(1);
+        trie.add("ab", Some(1));
+
+        let cursor = trie.cursor_mut();
+        let cursor = match cursor.descend(&"z".to_string()) {
+            Ok(_) => panic!("\"z\" was never added"),
+            Err(cursor) => cursor,
+        };
+        assert_eq!(cursor.path(), &[] as &[String]);
+        assert!(!cursor.current().is_key_end());
     }
 }